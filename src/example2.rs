@@ -1,23 +1,39 @@
 #![allow(clippy::type_complexity)]
 
 use halo2_proofs::{
-    arithmetic::FieldExt, circuit::*, dev::MockProver, pasta::Fp, plonk::*, poly::Rotation,
+    arithmetic::Field,
+    circuit::*,
+    dev::MockProver,
+    halo2curves::bn256::{Bn256, Fr as Fp, G1Affine},
+    plonk::*,
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+        Rotation,
+    },
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer},
 };
+use rand_core::OsRng;
 use std::marker::PhantomData;
 
 #[derive(Debug, Clone)]
 struct FiboConfig {
     advice: Column<Advice>,
+    constant: Column<Fixed>,
     selector: Selector,
     instance: Column<Instance>,
 }
 
-struct FiboChip<F: FieldExt> {
+struct FiboChip<F: Field> {
     config: FiboConfig,
     _marker: PhantomData<F>,
 }
 
-impl<F: FieldExt> FiboChip<F> {
+impl<F: Field> FiboChip<F> {
     fn construct(config: FiboConfig) -> Self {
         Self {
             config,
@@ -27,10 +43,12 @@ impl<F: FieldExt> FiboChip<F> {
 
     fn configure(meta: &mut ConstraintSystem<F>, instance: Column<Instance>) -> FiboConfig {
         let advice = meta.advice_column();
+        let constant = meta.fixed_column();
         let selector = meta.selector();
 
         meta.enable_equality(advice);
         meta.enable_equality(instance);
+        meta.enable_constant(constant);
 
         meta.create_gate("add", |meta| {
             //
@@ -50,50 +68,62 @@ impl<F: FieldExt> FiboChip<F> {
 
         FiboConfig {
             advice,
+            constant,
             selector,
             instance,
         }
     }
 
+    /// Assign the whole Fibonacci column.
+    ///
+    /// When `from_instance` is set, `f(0)` and `f(1)` are copied from instance rows 0 and 1
+    /// with `assign_advice_from_instance`, so the seeds are publicly constrained. Otherwise
+    /// they are pinned to the compile-time constants `a, b`.
     fn assign_column(
         &self,
         mut layouter: impl Layouter<F>,
-        a: Option<F>,
-        b: Option<F>,
+        a: F,
+        b: F,
+        from_instance: bool,
         nrows: usize,
     ) -> Result<AssignedCell<F, F>, Error> {
         let out = layouter.assign_region(
             || "whole column",
             |mut region| {
-                self.config.selector.enable(&mut region, 0)?;
-                self.config.selector.enable(&mut region, 1)?;
-
-                let mut a_cell = region.assign_advice(
-                    || "a",
-                    self.config.advice,
-                    0,
-                    || a.ok_or(Error::Synthesis),
-                )?;
-
-                let mut b_cell = region.assign_advice(
-                    || "b",
-                    self.config.advice,
-                    1,
-                    || b.ok_or(Error::Synthesis),
-                )?;
+                // Enable the gate only where the full 3-row window is assigned.
+                for row in 0..nrows.saturating_sub(2) {
+                    self.config.selector.enable(&mut region, row)?;
+                }
+
+                let (mut a_cell, mut b_cell) = if from_instance {
+                    let a_cell = region.assign_advice_from_instance(
+                        || "a",
+                        self.config.instance,
+                        0,
+                        self.config.advice,
+                        0,
+                    )?;
+                    let b_cell = region.assign_advice_from_instance(
+                        || "b",
+                        self.config.instance,
+                        1,
+                        self.config.advice,
+                        1,
+                    )?;
+                    (a_cell, b_cell)
+                } else {
+                    let a_cell =
+                        region.assign_advice_from_constant(|| "a", self.config.advice, 0, a)?;
+                    let b_cell =
+                        region.assign_advice_from_constant(|| "b", self.config.advice, 1, b)?;
+                    (a_cell, b_cell)
+                };
 
                 for row in 2..nrows {
-                    if row < nrows - 2 {
-                        self.config.selector.enable(&mut region, row)?;
-                    }
                     let c_val = a_cell.value().and_then(|a| b_cell.value().map(|b| *a + *b));
 
-                    let c_cell = region.assign_advice(
-                        || "c",
-                        self.config.advice,
-                        row,
-                        || c_val.ok_or(Error::Synthesis),
-                    )?;
+                    let c_cell =
+                        region.assign_advice(|| "c", self.config.advice, row, || c_val)?;
 
                     a_cell = b_cell;
                     b_cell = c_cell;
@@ -116,18 +146,34 @@ impl<F: FieldExt> FiboChip<F> {
     }
 }
 
+/// A Fibonacci circuit computing `f(N)`, with the sequence length fixed at compile time.
 #[derive(Default)]
-struct MyCircuit<F: FieldExt> {
-    a: Option<F>,
-    b: Option<F>,
+struct MyCircuit<F: Field, const N: usize> {
+    /// When set, the first two rows of the advice column are copied from instance rows 0 and 1
+    /// and the output is exposed at instance row 2; otherwise those rows are pinned to the
+    /// constants `1, 1` and the output is exposed at instance row 0.
+    from_instance: bool,
+    _marker: PhantomData<F>,
 }
 
-impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+/// Smallest `k` that fits an `N`-term Fibonacci column plus the prover's blinding rows.
+fn min_k(n: usize) -> u32 {
+    // f(0)..=f(n) occupies n + 1 advice rows; leave headroom for the blinding factors
+    // halo2 reserves at the bottom of each column.
+    let rows = n as u32 + 1 + 7;
+    (u32::BITS - (rows - 1).leading_zeros()).max(1)
+}
+
+impl<F: Field, const N: usize> Circuit<F> for MyCircuit<F, N> {
     type Config = FiboConfig;
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        // Preserve the seeding mode so key generation lays out the same circuit the prover uses.
+        Self {
+            from_instance: self.from_instance,
+            _marker: PhantomData,
+        }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -142,27 +188,140 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     ) -> Result<(), Error> {
         let chip = FiboChip::construct(config);
 
-        let out = chip.assign_column(layouter.namespace(|| "whole column"), self.a, self.b, 10)?;
+        let out = chip.assign_column(
+            layouter.namespace(|| "whole column"),
+            F::ONE,
+            F::ONE,
+            self.from_instance,
+            N + 1,
+        )?;
 
-        chip.expose_public(layouter.namespace(|| "out"), &out, 0)?;
+        let out_row = if self.from_instance { 2 } else { 0 };
+        chip.expose_public(layouter.namespace(|| "out"), &out, out_row)?;
 
         Ok(())
     }
 }
 
-fn main() {
-    let k = 5;
-
-    let a = Fp::from(1);
-    let b = Fp::from(1);
+/// Run the full KZG proving pipeline for `MyCircuit` and return the serialized proof.
+///
+/// Mirroring the bn256 flow of the other example, we sample the KZG parameters, run
+/// `keygen_vk`/`keygen_pk` against an empty (`without_witnesses`) circuit, create a proof over
+/// the public values `instance` with a Blake2b transcript writer, then round-trip it
+/// through `verify_proof` with a transcript reader and a `SingleStrategy`. Verification is
+/// asserted to succeed and the proof bytes are returned so callers can inspect the size.
+fn prove_and_verify<const N: usize>(k: u32, instance: Vec<Fp>, from_instance: bool) -> Vec<u8> {
+    let params = ParamsKZG::<Bn256>::setup(k, OsRng);
+
+    let empty_circuit = MyCircuit::<Fp, N> {
+        from_instance,
+        ..Default::default()
+    };
+    let vk = keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
 
-    let circuit = MyCircuit {
-        a: Some(a),
-        b: Some(b),
+    let circuit = MyCircuit::<Fp, N> {
+        from_instance,
+        ..Default::default()
     };
 
+    let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<Bn256>, _, _, _, _>(
+        &params,
+        &pk,
+        &[circuit],
+        &[&[&instance]],
+        OsRng,
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let proof = transcript.finalize();
+
+    let strategy = SingleStrategy::new(&params);
+    let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+    verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<Bn256>, _, _, _>(
+        &params,
+        pk.get_vk(),
+        strategy,
+        &[&[&instance]],
+        &mut transcript,
+    )
+    .expect("proof verification should not fail");
+
+    proof
+}
+
+fn main() {
+    // Prove f(9) = 55.
+    const N: usize = 9;
+    let k = min_k(N);
+
+    let circuit = MyCircuit::<Fp, N>::default();
+
     let pub_input = vec![Fp::from(55)];
 
     let prover = MockProver::run(k, &circuit, vec![pub_input]).unwrap();
     prover.assert_satisfied();
+
+    let proof = prove_and_verify::<N>(k, vec![Fp::from(55)], false);
+    println!("proof verified, size: {} bytes", proof.len());
+
+    // Same statement, but with the seeds taken from the public instance column, driven all the
+    // way through real proving so key generation keys against the instance-mode layout.
+    let circuit = MyCircuit::<Fp, N> {
+        from_instance: true,
+        ..Default::default()
+    };
+    let pub_input = vec![Fp::from(1), Fp::from(1), Fp::from(55)];
+    let prover = MockProver::run(k, &circuit, vec![pub_input.clone()]).unwrap();
+    prover.assert_satisfied();
+
+    let proof = prove_and_verify::<N>(k, pub_input, true);
+    println!("instance-mode proof verified, size: {} bytes", proof.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Materialize the sequence the same way the advice column does: push each row f(i) =
+    // f(i-1) + f(i-2) seeded from f(0) = f(1) = 1, then read out the requested index.
+    fn nth_fib(n: usize) -> u64 {
+        let mut column = vec![1u64, 1];
+        while column.len() <= n {
+            let len = column.len();
+            column.push(column[len - 1] + column[len - 2]);
+        }
+        column[n]
+    }
+
+    // The whole sequence lives in one region of `N + 1` rows, so `min_k` tracks the row count.
+    fn assert_column<const N: usize>() {
+        let circuit = MyCircuit::<Fp, N>::default();
+        let expected = Fp::from(nth_fib(N));
+        MockProver::run(min_k(N), &circuit, vec![vec![expected]])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn constants_mode_spans_short_and_long_columns() {
+        assert_column::<2>(); // boundary: only the seed window, no interior gate rows
+        assert_column::<7>();
+        assert_column::<12>();
+    }
+
+    #[test]
+    fn instance_mode_seeds_from_public_inputs() {
+        const N: usize = 12;
+        let circuit = MyCircuit::<Fp, N> {
+            from_instance: true,
+            ..Default::default()
+        };
+        // Seeds come from advice rows 0/1 (copied from instance rows 0/1); output at row 2.
+        let instance = vec![Fp::from(1), Fp::from(1), Fp::from(nth_fib(N))];
+        MockProver::run(min_k(N), &circuit, vec![instance])
+            .unwrap()
+            .assert_satisfied();
+    }
 }